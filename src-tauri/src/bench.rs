@@ -0,0 +1,118 @@
+//! Retrieval-quality benchmark harness.
+//!
+//! Workloads are plain JSON files of `{name, queries: [{text, relevant_paths}]}`. For
+//! each query we embed it, run vector retrieval, and score the results against
+//! `relevant_paths` (the ground truth), computing Recall@K, MRR, and mean latency. This
+//! gives maintainers a reproducible way to compare index configurations (chunking,
+//! embedding model, fusion weights) instead of eyeballing chat answers.
+
+use crate::lance_client::LanceClient;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+#[derive(Deserialize)]
+struct WorkloadQuery {
+  text: String,
+  relevant_paths: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Workload {
+  name: String,
+  queries: Vec<WorkloadQuery>,
+}
+
+#[derive(Serialize)]
+pub struct QueryMetrics {
+  text: String,
+  recall_at_k: f64,
+  reciprocal_rank: f64,
+  latency_ms: u128,
+}
+
+#[derive(Serialize)]
+pub struct Metrics {
+  recall_at_k: f64,
+  mrr: f64,
+  mean_latency_ms: f64,
+}
+
+#[derive(Serialize)]
+pub struct BenchmarkResults {
+  workload: String,
+  timestamp: u64,
+  metrics: Metrics,
+  per_query: Vec<QueryMetrics>,
+}
+
+const DEFAULT_TOP_K: usize = 3;
+
+/// Run `workload_path` against the live index and return aggregate + per-query metrics.
+pub async fn run_benchmark(lance: &LanceClient, workload_path: &Path) -> Result<BenchmarkResults> {
+  let raw = std::fs::read_to_string(workload_path)?;
+  let workload: Workload = serde_json::from_str(&raw)?;
+
+  let mut per_query = Vec::with_capacity(workload.queries.len());
+  for q in &workload.queries {
+    let started = Instant::now();
+
+    let embedding = crate::embed_text(&q.text).await?.unwrap_or_default();
+    let results = lance.query(embedding, DEFAULT_TOP_K).await.unwrap_or_default();
+    let latency_ms = started.elapsed().as_millis();
+
+    let retrieved_paths: Vec<String> = results
+      .iter()
+      .filter_map(|r| r.get("path").and_then(|p| p.as_str()).map(|s| s.to_string()))
+      .collect();
+    let relevant: HashSet<&String> = q.relevant_paths.iter().collect();
+
+    // Multiple top-K chunks commonly share the same file path, so count distinct
+    // relevant paths matched rather than raw chunk hits (which could exceed 1.0).
+    let distinct_retrieved_paths: HashSet<&String> = retrieved_paths.iter().collect();
+    let matched = distinct_retrieved_paths.into_iter().filter(|p| relevant.contains(*p)).count();
+    let recall_at_k = if q.relevant_paths.is_empty() {
+      0.0
+    } else {
+      matched as f64 / q.relevant_paths.len() as f64
+    };
+    let reciprocal_rank = retrieved_paths
+      .iter()
+      .position(|p| relevant.contains(p))
+      .map(|rank| 1.0 / (rank + 1) as f64)
+      .unwrap_or(0.0);
+
+    per_query.push(QueryMetrics {
+      text: q.text.clone(),
+      recall_at_k,
+      reciprocal_rank,
+      latency_ms,
+    });
+  }
+
+  let n = per_query.len().max(1) as f64;
+  let metrics = Metrics {
+    recall_at_k: per_query.iter().map(|q| q.recall_at_k).sum::<f64>() / n,
+    mrr: per_query.iter().map(|q| q.reciprocal_rank).sum::<f64>() / n,
+    mean_latency_ms: per_query.iter().map(|q| q.latency_ms as f64).sum::<f64>() / n,
+  };
+
+  Ok(BenchmarkResults {
+    workload: workload.name,
+    timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+    metrics,
+    per_query,
+  })
+}
+
+/// Write a benchmark run's results to `<data_dir>/bench_results/<workload>-<timestamp>.json`,
+/// returning the path written.
+pub fn write_results(data_dir: &Path, results: &BenchmarkResults) -> Result<std::path::PathBuf> {
+  let dir = data_dir.join("bench_results");
+  std::fs::create_dir_all(&dir)?;
+  let file = dir.join(format!("{}-{}.json", results.workload, results.timestamp));
+  std::fs::write(&file, serde_json::to_string_pretty(results)?)?;
+  Ok(file)
+}
@@ -0,0 +1,144 @@
+//! Splits extracted document text into overlapping chunks suitable for embedding.
+//!
+//! Chunks are measured in whitespace-separated tokens rather than bytes, and the
+//! splitter prefers to break on paragraph/line boundaries so a chunk doesn't cut a
+//! sentence in half when it can be avoided.
+
+/// One chunk of a document, with enough offset information to map back to the
+/// original text (stored on each Lance row, so a source citation can point at the
+/// chunk's position in the file).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+  pub chunk_index: usize,
+  pub start_offset: usize,
+  pub text: String,
+}
+
+const CHUNK_SIZE_TOKENS: usize = 512;
+const CHUNK_OVERLAP_TOKENS: usize = 64;
+
+/// Split `content` into overlapping chunks of ~[`CHUNK_SIZE_TOKENS`] tokens with
+/// ~[`CHUNK_OVERLAP_TOKENS`] tokens of overlap between consecutive chunks.
+///
+/// Splitting prefers paragraph boundaries (`\n\n`), falling back to line boundaries,
+/// so that a chunk boundary rarely lands mid-sentence.
+pub fn chunk_text(content: &str) -> Vec<Chunk> {
+  let units = split_into_units(content);
+  if units.is_empty() {
+    return Vec::new();
+  }
+
+  let mut chunks = Vec::new();
+  let mut unit_idx = 0;
+  let mut chunk_index = 0;
+
+  while unit_idx < units.len() {
+    let start_offset = units[unit_idx].0;
+    let mut token_count = 0;
+    let mut end_idx = unit_idx;
+    let mut end_offset = units[unit_idx].0 + units[unit_idx].1.len();
+
+    while end_idx < units.len() && token_count < CHUNK_SIZE_TOKENS {
+      token_count += units[end_idx].1.split_whitespace().count().max(1);
+      end_offset = units[end_idx].0 + units[end_idx].1.len();
+      end_idx += 1;
+    }
+
+    let text = content[start_offset..end_offset].trim().to_string();
+    if !text.is_empty() {
+      chunks.push(Chunk {
+        chunk_index,
+        start_offset,
+        text,
+      });
+      chunk_index += 1;
+    }
+
+    if end_idx >= units.len() {
+      break;
+    }
+
+    // Walk back from `end_idx` until we've shed ~CHUNK_OVERLAP_TOKENS tokens,
+    // so the next chunk starts with that much overlap.
+    let mut overlap_tokens = 0;
+    let mut next_idx = end_idx;
+    while next_idx > unit_idx + 1 && overlap_tokens < CHUNK_OVERLAP_TOKENS {
+      next_idx -= 1;
+      overlap_tokens += units[next_idx].1.split_whitespace().count().max(1);
+    }
+    unit_idx = next_idx.max(unit_idx + 1);
+  }
+
+  chunks
+}
+
+/// Break `content` into `(offset, text)` units along paragraph boundaries, falling
+/// back to line boundaries for paragraphs that don't exist (e.g. dense source files).
+fn split_into_units(content: &str) -> Vec<(usize, &str)> {
+  let mut units = Vec::new();
+  let mut offset = 0;
+  for paragraph in split_keep_delim(content, "\n\n") {
+    if paragraph.trim().is_empty() {
+      offset += paragraph.len();
+      continue;
+    }
+    if paragraph.split_whitespace().count() > CHUNK_SIZE_TOKENS {
+      for line in split_keep_delim(paragraph, "\n") {
+        if !line.trim().is_empty() {
+          units.push((offset, line));
+        }
+        offset += line.len();
+      }
+    } else {
+      units.push((offset, paragraph));
+      offset += paragraph.len();
+    }
+  }
+  units
+}
+
+/// Like `str::split`, but each yielded piece retains its trailing delimiter so
+/// offsets computed by summing piece lengths stay aligned with `content`.
+fn split_keep_delim<'a>(content: &'a str, delim: &str) -> Vec<&'a str> {
+  let mut pieces = Vec::new();
+  let mut rest = content;
+  while let Some(idx) = rest.find(delim) {
+    let end = idx + delim.len();
+    pieces.push(&rest[..end]);
+    rest = &rest[end..];
+  }
+  if !rest.is_empty() {
+    pieces.push(rest);
+  }
+  pieces
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn single_short_document_is_one_chunk() {
+    let chunks = chunk_text("hello world, this is a short document.");
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0].chunk_index, 0);
+    assert_eq!(chunks[0].start_offset, 0);
+  }
+
+  #[test]
+  fn empty_document_has_no_chunks() {
+    assert!(chunk_text("").is_empty());
+  }
+
+  #[test]
+  fn long_document_splits_with_overlap() {
+    let paragraph = "word ".repeat(100) + "\n\n";
+    let content = paragraph.repeat(10);
+    let chunks = chunk_text(&content);
+    assert!(chunks.len() > 1);
+    for window in chunks.windows(2) {
+      assert!(window[1].start_offset > window[0].start_offset);
+      assert!(window[1].start_offset < window[0].start_offset + window[0].text.len());
+    }
+  }
+}
@@ -0,0 +1,113 @@
+//! A long-lived handle to the Lance helper process (`src-tauri/lance_helper/index.js`).
+//!
+//! The helper used to be spawned fresh for every upsert/query, paying Node startup
+//! cost and reopening the Lance table each time. `LanceClient` spawns it once, keeps
+//! the table warm in the child process, and talks to it over newline-delimited JSON
+//! on stdin/stdout: each request is `{id, method, params}` and each response is
+//! matched back to its request by `id`.
+
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::Mutex;
+
+const HELPER_PATH: &str = "src-tauri/lance_helper/index.js";
+
+struct Session {
+  child: Child,
+  stdin: ChildStdin,
+  stdout: BufReader<ChildStdout>,
+  next_id: u64,
+}
+
+pub struct LanceClient {
+  session: Mutex<Session>,
+}
+
+impl LanceClient {
+  /// Spawn the helper as a long-lived child process, kept alive for the app's lifetime.
+  pub async fn spawn() -> Result<Self> {
+    let mut child = Command::new("node")
+      .arg(HELPER_PATH)
+      .arg("serve")
+      .stdin(Stdio::piped())
+      .stdout(Stdio::piped())
+      .stderr(Stdio::inherit())
+      .spawn()?;
+
+    let stdin = child.stdin.take().ok_or_else(|| anyhow!("lance helper: no stdin"))?;
+    let stdout = child.stdout.take().ok_or_else(|| anyhow!("lance helper: no stdout"))?;
+
+    Ok(Self {
+      session: Mutex::new(Session {
+        child,
+        stdin,
+        stdout: BufReader::new(stdout),
+        next_id: 1,
+      }),
+    })
+  }
+
+  async fn call(&self, method: &str, params: Value) -> Result<Value> {
+    let mut session = self.session.lock().await;
+    let id = session.next_id;
+    session.next_id += 1;
+
+    let request = json!({ "id": id, "method": method, "params": params });
+    let mut line = serde_json::to_string(&request)?;
+    line.push('\n');
+    session.stdin.write_all(line.as_bytes()).await?;
+    session.stdin.flush().await?;
+
+    loop {
+      let mut response_line = String::new();
+      let bytes_read = session.stdout.read_line(&mut response_line).await?;
+      if bytes_read == 0 {
+        return Err(anyhow!("lance helper closed its stdout"));
+      }
+      let response: Value = match serde_json::from_str(response_line.trim()) {
+        Ok(v) => v,
+        Err(_) => continue,
+      };
+      if response.get("id").and_then(|v| v.as_u64()) != Some(id) {
+        // Stale response for a prior call (shouldn't happen since calls are
+        // serialized by the mutex, but skip defensively rather than desync).
+        continue;
+      }
+      if let Some(err) = response.get("error") {
+        return Err(anyhow!("lance helper error: {}", err));
+      }
+      return Ok(response.get("result").cloned().unwrap_or(Value::Null));
+    }
+  }
+
+  pub async fn upsert(&self, row: Value) -> Result<()> {
+    self.call("upsert", row).await.map(|_| ())
+  }
+
+  pub async fn query(&self, embedding: Vec<f32>, top_k: usize) -> Result<Vec<Value>> {
+    let result = self.call("query", json!({ "embedding": embedding, "topK": top_k })).await?;
+    Ok(result.get("results").and_then(|r| r.as_array()).cloned().unwrap_or_default())
+  }
+
+  /// Term-frequency keyword search over stored chunk text, for hybrid retrieval alongside
+  /// vector search (see `retrieval::reciprocal_rank_fusion`).
+  pub async fn keyword(&self, query: &str, top_k: usize) -> Result<Vec<Value>> {
+    let result = self.call("keyword", json!({ "query": query, "topK": top_k })).await?;
+    Ok(result.get("results").and_then(|r| r.as_array()).cloned().unwrap_or_default())
+  }
+
+  pub async fn delete_path(&self, path: &str) -> Result<()> {
+    self.call("delete", json!({ "path": path })).await.map(|_| ())
+  }
+}
+
+impl Drop for LanceClient {
+  fn drop(&mut self) {
+    if let Ok(mut session) = self.session.try_lock() {
+      let _ = session.child.start_kill();
+    }
+  }
+}
@@ -1,10 +1,16 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod bench;
+mod chunking;
+mod lance_client;
+mod manifest;
+mod retrieval;
+mod watcher;
+
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use tauri::Manager;
 use tokio::process::Command;
-use tokio::io::{AsyncWriteExt, AsyncReadExt};
 use std::time::Duration;
 use walkdir::WalkDir;
 use uuid::Uuid;
@@ -12,6 +18,14 @@ use anyhow::Result;
 use std::fs;
 use std::process::Stdio;
 
+use lance_client::LanceClient;
+use manifest::{Manifest, ManifestStore};
+
+fn data_dir() -> PathBuf {
+  tauri::api::path::app_data_dir(&tauri::Config::default(), true)
+    .unwrap_or_else(|| PathBuf::from(".localragnpro-data"))
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 struct ChatMessage {
   id: String,
@@ -20,6 +34,11 @@ struct ChatMessage {
   timestamp: Option<String>,
 }
 
+/// In-flight streaming chat generations, keyed by chat id, so `cancel_chat` can kill
+/// the right `ollama run` child process.
+#[derive(Default)]
+struct ChatProcesses(tokio::sync::Mutex<std::collections::HashMap<String, tokio::process::Child>>);
+
 #[tauri::command]
 async fn plugin_init_app() -> Result<(), String> {
   // create data dir
@@ -31,9 +50,16 @@ async fn plugin_init_app() -> Result<(), String> {
   Ok(())
 }
 
-/// Index a folder: walk files, extract text, generate embeddings and upsert to Lance (node helper)
+/// Index a folder: walk files, extract text, chunk + generate embeddings and upsert to
+/// Lance (node helper). Files whose content digest matches the on-disk manifest are
+/// skipped entirely, so re-running this on an unchanged folder is cheap.
 #[tauri::command]
-async fn index_folder(folder: String) -> Result<(), String> {
+async fn index_folder(
+  folder: String,
+  lance: tauri::State<'_, LanceClient>,
+  manifest_store: tauri::State<'_, ManifestStore>,
+  app: tauri::AppHandle,
+) -> Result<(), String> {
   let folder_path = std::path::Path::new(&folder);
   if !folder_path.exists() {
     return Err("Folder not found".into());
@@ -44,43 +70,72 @@ async fn index_folder(folder: String) -> Result<(), String> {
   for entry in WalkDir::new(&folder).into_iter().filter_map(|e| e.ok()) {
     if entry.file_type().is_file() {
       let path = entry.path();
-      if let Some(ext) = path.extension().and_then(|s| s.to_str()) {
-        match ext.to_lowercase().as_str() {
-          "txt" | "md" | "rs" | "js" | "ts" | "py" | "java" => {
-            if let Ok(s) = std::fs::read_to_string(path) {
-              docs.push((path.display().to_string(), s))
-            }
-          }
-          "pdf" => {
-            match extract_text_from_pdf(path).await {
-              Ok(text) => docs.push((path.display().to_string(), text)),
-              Err(_) => {
-                // fallback note
-                docs.push((path.display().to_string(), "[PDF] (no text extracted; install pdftotext)".to_string()))
-              }
-            }
-          }
-          _ => {}
-        }
+      if let Some(content) = read_supported_file(path).await {
+        docs.push((path.display().to_string(), content));
       }
     }
   }
 
-  // For each doc, call Ollama embed, then upsert into Lance via node helper
+  // Lock the shared manifest once for the whole run (rather than per file) so this
+  // doesn't degrade to O(N^2) I/O on a large folder, and save once at the end.
+  let mut manifest = manifest_store.lock().await;
   for (path, content) in docs {
     tokio::time::sleep(Duration::from_millis(30)).await;
     if content.trim().is_empty() {
       continue;
     }
-    let _ = generate_and_upsert_embedding(&path, &content).await;
+    reindex_file(&lance, &mut manifest, &path, &content).await;
+  }
+  if let Err(e) = manifest_store.save(&manifest) {
+    println!("Failed to save index manifest: {}", e);
   }
+  drop(manifest);
 
-  // Spawn a file watcher for auto-reload (non-blocking)
-  let _ = spawn_folder_watcher(folder).await;
+  // Spawn a debounced file watcher that incrementally reindexes on change
+  let _ = watcher::spawn_folder_watcher(folder, app).await;
 
   Ok(())
 }
 
+/// Read and extract text from a file if its extension is one of the supported document
+/// types, returning `None` for anything `index_folder` doesn't know how to embed.
+async fn read_supported_file(path: &std::path::Path) -> Option<String> {
+  let ext = path.extension().and_then(|s| s.to_str())?.to_lowercase();
+  match ext.as_str() {
+    "txt" | "md" | "rs" | "js" | "ts" | "py" | "java" => std::fs::read_to_string(path).ok(),
+    "pdf" => match extract_text_from_pdf(path).await {
+      Ok(text) => Some(text),
+      // fallback note
+      Err(_) => Some("[PDF] (no text extracted; install pdftotext)".to_string()),
+    },
+    _ => None,
+  }
+}
+
+/// Re-embed `path` if its content digest differs from `manifest` (or is new), updating
+/// `manifest` in place either way. Returns whether the file was (re-)embedded. Callers
+/// own locking and persisting the shared `Manifest` (see `ManifestStore`).
+async fn reindex_file(lance: &LanceClient, manifest: &mut Manifest, path: &str, content: &str) -> bool {
+  let digest = manifest::hash_bytes(content.as_bytes());
+  if !manifest.is_stale(path, &digest) {
+    return false;
+  }
+
+  let _ = lance.delete_path(path).await;
+  for chunk in chunking::chunk_text(content) {
+    let _ = generate_and_upsert_embedding(lance, path, chunk.chunk_index, chunk.start_offset, &chunk.text).await;
+  }
+  manifest.update(path, digest);
+  true
+}
+
+/// Remove all rows and the manifest entry for `path`, used when a watched file is
+/// deleted. Callers own locking and persisting the shared `Manifest`.
+async fn remove_path_from_index(lance: &LanceClient, manifest: &mut Manifest, path: &str) {
+  let _ = lance.delete_path(path).await;
+  manifest.remove(path);
+}
+
 async fn extract_text_from_pdf(path: &std::path::Path) -> Result<String> {
   // Try to use pdftotext (poppler-utils) for best results
   let p = path.to_string_lossy().to_string();
@@ -107,7 +162,7 @@ async fn extract_text_from_pdf(path: &std::path::Path) -> Result<String> {
   Err(anyhow::anyhow!("pdftotext produced empty output"))
 }
 
-async fn generate_and_upsert_embedding(path: &str, content: &str) -> Result<()> {
+async fn generate_and_upsert_embedding(lance: &LanceClient, path: &str, chunk_index: usize, start_offset: usize, content: &str) -> Result<()> {
   // Use Ollama CLI to embed with nomic-embed-text
   let output = Command::new("ollama")
     .args(&["embed", "nomic-embed-text", "--text"])
@@ -116,7 +171,7 @@ async fn generate_and_upsert_embedding(path: &str, content: &str) -> Result<()>
     .await?;
 
   if !output.status.success() {
-    println!("Ollama embed failed for {}: {:?}", path, output);
+    println!("Ollama embed failed for {} chunk {}: {:?}", path, chunk_index, output);
     return Ok(());
   }
 
@@ -134,121 +189,122 @@ async fn generate_and_upsert_embedding(path: &str, content: &str) -> Result<()>
   let upsert_payload = serde_json::json!({
     "id": id,
     "path": path,
+    "chunk_index": chunk_index,
+    "start_offset": start_offset,
     "text": content,
     "embedding": embedding_vec
   });
 
-  // Call node helper: send JSON via stdin and read response
-  let helper_path = "src-tauri/lance_helper/index.js"; // relative to repo root (dev). For packaged apps, ensure helper included in resources.
-  let mut child = Command::new("node")
-    .arg(helper_path)
-    .arg("upsert")
-    .stdin(Stdio::piped())
-    .stdout(Stdio::piped())
-    .stderr(Stdio::piped())
-    .spawn()?;
-
-  if let Some(mut stdin) = child.stdin.take() {
-    let payload_str = serde_json::to_string(&upsert_payload)?;
-    stdin.write_all(payload_str.as_bytes()).await?;
-    stdin.shutdown().await?;
-  }
-
-  // read stdout (not strictly necessary for upsert)
-  let out = child.wait_with_output().await?;
-  if !out.status.success() {
-    println!("Lance helper upsert failed: {:?}", String::from_utf8_lossy(&out.stderr));
+  if let Err(e) = lance.upsert(upsert_payload).await {
+    println!("Lance helper upsert failed: {:?}", e);
   }
 
   Ok(())
 }
 
-async fn spawn_folder_watcher(folder: String) -> Result<()> {
-  // Spawn a file watcher using notify that triggers re-index on changes
-  tokio::task::spawn_blocking(move || {
-    use notify::{RecommendedWatcher, RecursiveMode, Watcher, EventKind};
-    let folder_clone = folder.clone();
-    let mut watcher: RecommendedWatcher = notify::recommended_watcher(move |res| {
-      match res {
-        Ok(event) => {
-          if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) {
-            println!("Change detected: {:?}. You may want to re-index: {}", event.paths, folder_clone);
-            // For now we don't auto-reindex to avoid runaway cycles.
-          }
-        }
-        Err(e) => println!("watch error: {:?}", e),
-      }
-    }).expect("failed to create watcher");
-    watcher.watch(std::path::Path::new(&folder_clone), notify::RecursiveMode::Recursive).expect("watch failed");
-    // keep thread alive
-    loop { std::thread::sleep(std::time::Duration::from_secs(3600)); }
-  });
-  Ok(())
+const CONTEXT_TOP_K: usize = 3;
+/// How many candidates each retrieval mode contributes to hybrid mode before RRF
+/// fusion trims down to `CONTEXT_TOP_K` — wider than the final context so a document
+/// that ranks highly in only one of the two lists still has a chance to be fused in.
+const HYBRID_CANDIDATES_PER_MODE: usize = 10;
+
+/// Retrieval mode for `chat_query`'s context lookup.
+#[derive(PartialEq)]
+enum RetrievalMode {
+  Vector,
+  Keyword,
+  Hybrid,
 }
 
-#[tauri::command]
-async fn chat_query(query: String, history: Vec<ChatMessage>) -> Result<serde_json::Value, String> {
-  // 1) embed the query
+impl RetrievalMode {
+  /// Defaults to `Vector` (the pre-existing behavior) so callers that don't pass a
+  /// `mode` at all aren't silently switched onto the hybrid path. Pass `"hybrid"`
+  /// explicitly to opt into fused keyword + vector retrieval.
+  fn parse(mode: Option<String>) -> Self {
+    match mode.as_deref() {
+      Some("keyword") => RetrievalMode::Keyword,
+      Some("hybrid") => RetrievalMode::Hybrid,
+      _ => RetrievalMode::Vector,
+    }
+  }
+}
+
+/// Embed `query` with the same embedding model used at index time, unless `mode` is
+/// pure keyword search (which needs no embedding at all).
+async fn embed_query(query: &str, mode: &RetrievalMode) -> Result<Option<Vec<f32>>, String> {
+  if *mode == RetrievalMode::Keyword {
+    return Ok(None);
+  }
+  embed_text(query).await.map_err(|e| e.to_string())
+}
+
+/// Embed arbitrary text with the same `nomic-embed-text` model used at index time.
+/// Shared by [`embed_query`] and the benchmark harness (`bench::run_benchmark`), which
+/// needs to embed workload queries the same way chat queries are embedded.
+pub(crate) async fn embed_text(text: &str) -> Result<Option<Vec<f32>>> {
   let embed_out = Command::new("ollama")
     .args(&["embed", "nomic-embed-text", "--text"])
-    .arg(&query)
+    .arg(text)
     .output()
-    .await
-    .map_err(|e| e.to_string())?;
+    .await?;
 
   if !embed_out.status.success() {
-    return Err("Failed to embed query".into());
+    return Err(anyhow::anyhow!("Failed to embed text"));
   }
   let embed_stdout = String::from_utf8_lossy(&embed_out.stdout);
-  let embedding_vec: Option<Vec<f32>> = match serde_json::from_str::<serde_json::Value>(&embed_stdout) {
+  Ok(match serde_json::from_str::<serde_json::Value>(&embed_stdout) {
     Ok(v) => v.get("embedding").and_then(|e| e.as_array().map(|arr| {
       arr.iter().filter_map(|x| x.as_f64().map(|f| f as f32)).collect::<Vec<f32>>()
     })),
     Err(_) => None,
-  };
-
-  // 2) Query Lance (via node helper) for top-K contexts
-  let mut top_contexts: Vec<serde_json::Value> = Vec::new();
-  if let Some(emb) = embedding_vec {
-    let q_payload = serde_json::json!({
-      "embedding": emb,
-      "topK": 3
-    });
-
-    let helper_path = "src-tauri/lance_helper/index.js";
-    let mut child = Command::new("node")
-      .arg(helper_path)
-      .arg("query")
-      .stdin(Stdio::piped())
-      .stdout(Stdio::piped())
-      .spawn()
-      .map_err(|e| e.to_string())?;
-
-    if let Some(mut stdin) = child.stdin.take() {
-      let payload_str = serde_json::to_string(&q_payload).map_err(|e| e.to_string())?;
-      stdin.write_all(payload_str.as_bytes()).await.map_err(|e| e.to_string())?;
-      stdin.shutdown().await.map_err(|e| e.to_string())?;
-    }
+  })
+}
 
-    let out = child.wait_with_output().await.map_err(|e| e.to_string())?;
-    if out.status.success() {
-      if let Ok(s) = String::from_utf8(out.stdout) {
-        if let Ok(v) = serde_json::from_str::<serde_json::Value>(&s) {
-          if let Some(items) = v.get("results").and_then(|r| r.as_array()) {
-            for it in items {
-              top_contexts.push(it.clone())
-            }
-          }
+/// Retrieve the top-K context chunks for `query` using the selected retrieval mode.
+async fn retrieve_contexts(
+  lance: &LanceClient,
+  query: &str,
+  mode: &RetrievalMode,
+  embedding_vec: Option<Vec<f32>>,
+) -> Vec<serde_json::Value> {
+  match mode {
+    RetrievalMode::Vector => {
+      if let Some(emb) = embedding_vec {
+        match lance.query(emb, CONTEXT_TOP_K).await {
+          Ok(results) => return results,
+          Err(e) => println!("Lance query failed: {}", e),
         }
       }
-    } else {
-      println!("Lance query failed: {}", String::from_utf8_lossy(&out.stderr));
+      Vec::new()
+    }
+    RetrievalMode::Keyword => match lance.keyword(query, CONTEXT_TOP_K).await {
+      Ok(results) => results,
+      Err(e) => {
+        println!("Lance keyword search failed: {}", e);
+        Vec::new()
+      }
+    },
+    RetrievalMode::Hybrid => {
+      let vector_results = match embedding_vec {
+        Some(emb) => lance.query(emb, HYBRID_CANDIDATES_PER_MODE).await.unwrap_or_else(|e| {
+          println!("Lance query failed: {}", e);
+          Vec::new()
+        }),
+        None => Vec::new(),
+      };
+      let keyword_results = lance.keyword(query, HYBRID_CANDIDATES_PER_MODE).await.unwrap_or_else(|e| {
+        println!("Lance keyword search failed: {}", e);
+        Vec::new()
+      });
+      retrieval::reciprocal_rank_fusion(vec![vector_results, keyword_results], CONTEXT_TOP_K)
     }
   }
+}
 
-  // 3) Build prompt using top contexts & history
+/// Build the RAG prompt from retrieved contexts, conversation history, and the query.
+fn build_prompt(top_contexts: &[serde_json::Value], history: &[ChatMessage], query: &str) -> String {
   let mut prompt = String::from("You are LocalRAG Pro, a helpful assistant. Use the provided context to answer the question.\n\nCONTEXT:\n");
-  for c in &top_contexts {
+  for c in top_contexts {
     if let Some(text) = c.get("text").and_then(|t| t.as_str()) {
       prompt.push_str("---\n");
       prompt.push_str(text);
@@ -260,10 +316,19 @@ async fn chat_query(query: String, history: Vec<ChatMessage>) -> Result<serde_js
     prompt.push_str(&format!("{}: {}\n", m.role, m.content));
   }
   prompt.push_str("\nUser: ");
-  prompt.push_str(&query);
+  prompt.push_str(query);
   prompt.push_str("\nAssistant:");
+  prompt
+}
+
+#[tauri::command]
+async fn chat_query(query: String, history: Vec<ChatMessage>, mode: Option<String>, lance: tauri::State<'_, LanceClient>) -> Result<serde_json::Value, String> {
+  let mode = RetrievalMode::parse(mode);
+  let embedding_vec = embed_query(&query, &mode).await?;
+  let top_contexts = retrieve_contexts(&lance, &query, &mode, embedding_vec).await;
+  let prompt = build_prompt(&top_contexts, &history, &query);
 
-  // 4) Call Ollama run with prompt
+  // Call Ollama run with prompt
   let mut child = Command::new("ollama")
     .arg("run")
     .arg("llama3.2")
@@ -284,6 +349,64 @@ async fn chat_query(query: String, history: Vec<ChatMessage>) -> Result<serde_js
   Ok(serde_json::json!({ "answer": answer, "sources": top_contexts }))
 }
 
+/// Streaming variant of [`chat_query`]: spawns the model with piped stdout and emits
+/// each decoded line as a `chat-token` event (tagged with `chatId`) as it arrives,
+/// instead of blocking until the full answer is generated. The child process is kept
+/// in `ChatProcesses` so `cancel_chat` can kill it mid-generation.
+#[tauri::command]
+async fn chat_query_stream(
+  chat_id: String,
+  query: String,
+  history: Vec<ChatMessage>,
+  mode: Option<String>,
+  lance: tauri::State<'_, LanceClient>,
+  processes: tauri::State<'_, ChatProcesses>,
+  app: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+  let mode = RetrievalMode::parse(mode);
+  let embedding_vec = embed_query(&query, &mode).await?;
+  let top_contexts = retrieve_contexts(&lance, &query, &mode, embedding_vec).await;
+  let prompt = build_prompt(&top_contexts, &history, &query);
+
+  let mut child = Command::new("ollama")
+    .arg("run")
+    .arg("llama3.2")
+    .arg("--prompt")
+    .arg(&prompt)
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()
+    .map_err(|e| e.to_string())?;
+
+  let stdout = child.stdout.take().ok_or("failed to capture ollama stdout")?;
+  processes.0.lock().await.insert(chat_id.clone(), child);
+
+  let mut lines = tokio::io::AsyncBufReadExt::lines(tokio::io::BufReader::new(stdout));
+  let mut answer = String::new();
+  while let Ok(Some(line)) = lines.next_line().await {
+    if !answer.is_empty() {
+      answer.push('\n');
+    }
+    answer.push_str(&line);
+    let _ = app.emit_all("chat-token", serde_json::json!({ "chatId": chat_id, "token": line }));
+  }
+
+  // Reap the child now that stdout has closed (EOF or `cancel_chat` killed it).
+  if let Some(mut child) = processes.0.lock().await.remove(&chat_id) {
+    let _ = child.wait().await;
+  }
+
+  Ok(serde_json::json!({ "answer": answer, "sources": top_contexts }))
+}
+
+#[tauri::command]
+async fn cancel_chat(chat_id: String, processes: tauri::State<'_, ChatProcesses>) -> Result<(), String> {
+  if let Some(mut child) = processes.0.lock().await.remove(&chat_id) {
+    child.start_kill().map_err(|e| e.to_string())?;
+  }
+  Ok(())
+}
+
 #[tauri::command]
 async fn save_chat(messages: Vec<ChatMessage>) -> Result<(), String> {
   let data_dir = tauri::api::path::app_data_dir(&tauri::Config::default(), true)
@@ -343,16 +466,38 @@ async fn get_license() -> Result<String, String> {
   }
 }
 
+/// Run a retrieval-quality benchmark workload and write the results JSON into the app
+/// data dir. See `bench` for the workload file schema and computed metrics.
+#[tauri::command]
+async fn run_benchmark(workload_path: String, lance: tauri::State<'_, LanceClient>) -> Result<serde_json::Value, String> {
+  let results = bench::run_benchmark(&lance, std::path::Path::new(&workload_path))
+    .await
+    .map_err(|e| e.to_string())?;
+  let written = bench::write_results(&data_dir(), &results).map_err(|e| e.to_string())?;
+  Ok(serde_json::json!({ "resultsPath": written.display().to_string() }))
+}
+
 fn main() {
   tauri::Builder::default()
+    .setup(|app| {
+      let lance = tauri::async_runtime::block_on(LanceClient::spawn())
+        .expect("failed to start Lance helper process");
+      app.manage(lance);
+      app.manage(ManifestStore::load(&data_dir()));
+      app.manage(ChatProcesses::default());
+      Ok(())
+    })
     .invoke_handler(tauri::generate_handler![
       plugin_init_app,
       index_folder,
       chat_query,
+      chat_query_stream,
+      cancel_chat,
       save_chat,
       export_chat,
       set_license,
-      get_license
+      get_license,
+      run_benchmark
     ])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
@@ -0,0 +1,106 @@
+//! Tracks a SHA-256 content digest per indexed file so `index_folder` can skip
+//! files that haven't changed since the last run instead of re-embedding them.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Manifest {
+  /// path -> hex-encoded SHA-256 digest of the file's extracted text at last index time.
+  entries: HashMap<String, String>,
+}
+
+impl Manifest {
+  pub fn load(path: &Path) -> Self {
+    match std::fs::read_to_string(path) {
+      Ok(s) => serde_json::from_str(&s).unwrap_or_default(),
+      Err(_) => Manifest::default(),
+    }
+  }
+
+  pub fn save(&self, path: &Path) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+      std::fs::create_dir_all(parent)?;
+    }
+    let content = serde_json::to_string_pretty(self).unwrap_or_default();
+    std::fs::write(path, content)
+  }
+
+  /// Returns `true` when `digest` differs from (or is absent from) the recorded
+  /// digest for `file_path`, meaning the file needs (re-)embedding.
+  pub fn is_stale(&self, file_path: &str, digest: &str) -> bool {
+    self.entries.get(file_path).map(|d| d.as_str()) != Some(digest)
+  }
+
+  pub fn update(&mut self, file_path: &str, digest: String) {
+    self.entries.insert(file_path.to_string(), digest);
+  }
+
+  pub fn remove(&mut self, file_path: &str) {
+    self.entries.remove(file_path);
+  }
+}
+
+pub fn hash_bytes(bytes: &[u8]) -> String {
+  let mut hasher = Sha256::new();
+  hasher.update(bytes);
+  format!("{:x}", hasher.finalize())
+}
+
+pub fn manifest_path(data_dir: &Path) -> PathBuf {
+  data_dir.join("index_manifest.json")
+}
+
+/// The app-wide manifest, held behind a mutex in Tauri managed state so `index_folder`
+/// and the folder watcher's incremental reindex never interleave their load-modify-save
+/// cycles and silently drop each other's entries.
+pub struct ManifestStore {
+  path: PathBuf,
+  inner: tokio::sync::Mutex<Manifest>,
+}
+
+impl ManifestStore {
+  pub fn load(data_dir: &Path) -> Self {
+    let path = manifest_path(data_dir);
+    let manifest = Manifest::load(&path);
+    Self {
+      path,
+      inner: tokio::sync::Mutex::new(manifest),
+    }
+  }
+
+  pub async fn lock(&self) -> tokio::sync::MutexGuard<'_, Manifest> {
+    self.inner.lock().await
+  }
+
+  pub fn save(&self, manifest: &Manifest) -> std::io::Result<()> {
+    manifest.save(&self.path)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn unknown_path_is_stale() {
+    let manifest = Manifest::default();
+    assert!(manifest.is_stale("foo.txt", "deadbeef"));
+  }
+
+  #[test]
+  fn matching_digest_is_not_stale() {
+    let mut manifest = Manifest::default();
+    manifest.update("foo.txt", "deadbeef".to_string());
+    assert!(!manifest.is_stale("foo.txt", "deadbeef"));
+  }
+
+  #[test]
+  fn changed_digest_is_stale() {
+    let mut manifest = Manifest::default();
+    manifest.update("foo.txt", "deadbeef".to_string());
+    assert!(manifest.is_stale("foo.txt", "cafebabe"));
+  }
+}
@@ -0,0 +1,66 @@
+//! Fuses multiple ranked result lists (e.g. vector search and keyword search) into one
+//! ranking via Reciprocal Rank Fusion, so `chat_query`'s hybrid mode benefits from both
+//! semantic recall and exact-term/rare-token matches that embeddings tend to miss.
+
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// RRF's smoothing constant: discourages any single list's rank-1 result from
+/// dominating the fused score, per the original Cormack et al. formulation.
+const RRF_K: f64 = 60.0;
+
+/// Fuse `lists` of ranked results (each already sorted best-first) into a single
+/// ranking by summing `1 / (RRF_K + rank)` per result across every list it appears in,
+/// then sorting descending by that fused score. Results are deduplicated by `id`,
+/// keeping the first copy seen.
+pub fn reciprocal_rank_fusion(lists: Vec<Vec<Value>>, top_k: usize) -> Vec<Value> {
+  let mut scores: HashMap<String, f64> = HashMap::new();
+  let mut rows: HashMap<String, Value> = HashMap::new();
+
+  for list in lists {
+    for (rank, row) in list.into_iter().enumerate() {
+      let id = match row.get("id").and_then(|v| v.as_str()) {
+        Some(id) => id.to_string(),
+        None => continue,
+      };
+      let score = 1.0 / (RRF_K + (rank + 1) as f64);
+      *scores.entry(id.clone()).or_insert(0.0) += score;
+      rows.entry(id).or_insert(row);
+    }
+  }
+
+  let mut fused: Vec<(String, f64)> = scores.into_iter().collect();
+  fused.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+  fused
+    .into_iter()
+    .take(top_k)
+    .filter_map(|(id, _)| rows.remove(&id))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn document_in_both_lists_outranks_single_list_hits() {
+    let vector = vec![json!({"id": "a"}), json!({"id": "b"})];
+    let keyword = vec![json!({"id": "b"}), json!({"id": "c"})];
+    let fused = reciprocal_rank_fusion(vec![vector, keyword], 3);
+    assert_eq!(fused[0]["id"], "b");
+  }
+
+  #[test]
+  fn respects_top_k() {
+    let list = vec![json!({"id": "a"}), json!({"id": "b"}), json!({"id": "c"})];
+    let fused = reciprocal_rank_fusion(vec![list], 2);
+    assert_eq!(fused.len(), 2);
+  }
+
+  #[test]
+  fn empty_lists_yield_no_results() {
+    assert!(reciprocal_rank_fusion(vec![], 5).is_empty());
+  }
+}
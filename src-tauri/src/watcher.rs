@@ -0,0 +1,106 @@
+//! Debounced incremental reindexing driven by filesystem change events.
+//!
+//! `spawn_folder_watcher` used to only log that a change was detected. This collapses a
+//! burst of events for the same path (e.g. an editor saving several times in a row) into
+//! a single action after ~[`DEBOUNCE_MILLIS`] of quiescence, then re-runs extraction +
+//! chunked embedding for `Create`/`Modify` events (skipping it if the content digest is
+//! unchanged, via [`crate::reindex_file`]), or removes the file's rows on `Remove`.
+
+use notify::EventKind;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tauri::Manager;
+
+const DEBOUNCE_MILLIS: u64 = 500;
+const POLL_MILLIS: u64 = 100;
+
+pub async fn spawn_folder_watcher(folder: String, app: tauri::AppHandle) -> anyhow::Result<()> {
+  let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<(PathBuf, EventKind)>();
+
+  tokio::task::spawn_blocking(move || {
+    use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+    let mut watcher: RecommendedWatcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+      match res {
+        Ok(event) => {
+          for path in event.paths.clone() {
+            let _ = tx.send((path, event.kind));
+          }
+        }
+        Err(e) => println!("watch error: {:?}", e),
+      }
+    }).expect("failed to create watcher");
+    watcher.watch(std::path::Path::new(&folder), RecursiveMode::Recursive).expect("watch failed");
+    // keep thread alive
+    loop { std::thread::sleep(std::time::Duration::from_secs(3600)); }
+  });
+
+  tokio::spawn(async move {
+    let mut pending: HashMap<PathBuf, (Instant, EventKind)> = HashMap::new();
+
+    loop {
+      tokio::select! {
+        event = rx.recv() => {
+          match event {
+            Some((path, kind)) => { pending.insert(path, (Instant::now(), kind)); }
+            None => break,
+          }
+        }
+        _ = tokio::time::sleep(Duration::from_millis(POLL_MILLIS)) => {}
+      }
+
+      let now = Instant::now();
+      let settled: Vec<PathBuf> = pending
+        .iter()
+        .filter(|(_, (seen, _))| now.duration_since(*seen) >= Duration::from_millis(DEBOUNCE_MILLIS))
+        .map(|(path, _)| path.clone())
+        .collect();
+
+      for path in settled {
+        if let Some((_, kind)) = pending.remove(&path) {
+          handle_change(&app, &path, kind).await;
+        }
+      }
+    }
+  });
+
+  Ok(())
+}
+
+async fn handle_change(app: &tauri::AppHandle, path: &std::path::Path, kind: EventKind) {
+  let path_str = path.display().to_string();
+  let lance = app.state::<crate::lance_client::LanceClient>();
+  let manifest_store = app.state::<crate::manifest::ManifestStore>();
+
+  if matches!(kind, EventKind::Remove(_)) {
+    {
+      let mut manifest = manifest_store.lock().await;
+      crate::remove_path_from_index(&lance, &mut manifest, &path_str).await;
+      if let Err(e) = manifest_store.save(&manifest) {
+        println!("Failed to save index manifest: {}", e);
+      }
+    }
+    let _ = app.emit_all("reindex-progress", serde_json::json!({ "path": path_str, "action": "removed" }));
+    return;
+  }
+
+  if !matches!(kind, EventKind::Create(_) | EventKind::Modify(_)) {
+    return;
+  }
+
+  match crate::read_supported_file(path).await {
+    Some(content) if !content.trim().is_empty() => {
+      let reindexed = {
+        let mut manifest = manifest_store.lock().await;
+        let reindexed = crate::reindex_file(&lance, &mut manifest, &path_str, &content).await;
+        if let Err(e) = manifest_store.save(&manifest) {
+          println!("Failed to save index manifest: {}", e);
+        }
+        reindexed
+      };
+      let action = if reindexed { "reindexed" } else { "unchanged" };
+      let _ = app.emit_all("reindex-progress", serde_json::json!({ "path": path_str, "action": action }));
+    }
+    _ => {}
+  }
+}